@@ -0,0 +1,416 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+
+use clap::ValueEnum;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use duckdb::arrow::record_batch::RecordBatch;
+use duckdb::{Connection, ToSql};
+
+use crate::format::{self, OutputFormat};
+
+/// Named query parameters collected via `--param`/`\set`, bound as `$name` placeholders.
+pub type Params = BTreeMap<String, String>;
+
+/// Parse repeated `name=value` strings (as taken by `--param`) into [`Params`].
+pub fn parse_params(pairs: &[String]) -> Result<Params> {
+    let mut params = Params::new();
+    for pair in pairs {
+        let (name, value) = pair
+            .split_once('=')
+            .ok_or_else(|| eyre!("invalid --param `{pair}`, expected name=value"))?;
+        params.insert(name.to_string(), value.to_string());
+    }
+    Ok(params)
+}
+
+/// Table/column identifiers can't be bound as prepared-statement parameters, so
+/// whenever one has to be interpolated into SQL we check it against this shape first.
+fn validate_identifier(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let starts_ok = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    let rest_ok = chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if starts_ok && rest_ok {
+        Ok(())
+    } else {
+        Err(eyre!(
+            "invalid identifier `{name}`: expected it to match [A-Za-z_][A-Za-z0-9_]*"
+        ))
+    }
+}
+
+/// Key each parameter as `$name`, as owned pairs; [`to_sql_refs`] then borrows them as
+/// the `(&str, &dyn ToSql)` pairs DuckDB's named binding expects.
+fn bind_named(params: &Params) -> Vec<(String, String)> {
+    params
+        .iter()
+        .map(|(name, value)| (format!("${name}"), value.clone()))
+        .collect()
+}
+
+fn to_sql_refs(bound: &[(String, String)]) -> Vec<(&str, &dyn ToSql)> {
+    bound
+        .iter()
+        .map(|(name, value)| (name.as_str(), value as &dyn ToSql))
+        .collect()
+}
+
+/// Quote `s` as a single-quoted SQL string literal. Used for file paths (and the CSV
+/// delimiter) fed to `COPY`/`read_*`, which DuckDB needs as a literal at bind time (to parse
+/// `COPY`'s own grammar, or to sniff the file's schema) rather than as a
+/// prepared-statement parameter substituted at execute time.
+fn sql_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Open (or create) a DuckDB database
+pub fn open_db(path: &str) -> Result<Connection> {
+    Connection::open(path).wrap_err_with(|| format!("failed to open DuckDB database at {path}"))
+}
+
+/// Run a query and collect its result as Arrow batches, binding `params` as `$name` placeholders.
+pub fn execute_query(conn: &Connection, sql: &str, params: &Params) -> Result<Vec<RecordBatch>> {
+    let mut stmt = conn
+        .prepare(sql)
+        .wrap_err_with(|| format!("failed to prepare query: {sql}"))?;
+
+    let arrow = if params.is_empty() {
+        stmt.query_arrow([]).wrap_err("arrow query failed")?
+    } else {
+        let bound = bind_named(params);
+        stmt.query_arrow(to_sql_refs(&bound).as_slice())
+            .wrap_err("arrow query failed")?
+    };
+
+    Ok(arrow.collect())
+}
+
+/// Run a `;`-separated script as a single transaction, rolling back on the
+/// first failing statement and printing a status line for each one.
+pub fn run_script(conn: &mut Connection, script: &str, params: &Params) -> Result<()> {
+    let statements = crate::sql::split_statements(script);
+    if statements.is_empty() {
+        println!("OK (no statements)");
+        return Ok(());
+    }
+
+    let tx = conn.transaction().wrap_err("failed to start transaction")?;
+    let total = statements.len();
+
+    for (idx, stmt) in statements.iter().enumerate() {
+        match execute_query(&tx, stmt, params) {
+            Ok(batches) => {
+                let rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+                println!("[{}/{total}] OK ({rows} rows)", idx + 1);
+            }
+            Err(err) => {
+                eprintln!("[{}/{total}] error: {err:?}", idx + 1);
+                tx.rollback().wrap_err("failed to roll back transaction")?;
+                return Err(eyre!(
+                    "script failed at statement {} of {total}; transaction rolled back",
+                    idx + 1
+                ));
+            }
+        }
+    }
+
+    tx.commit().wrap_err("failed to commit transaction")?;
+    Ok(())
+}
+
+/// Run a query and print/write its result in the requested output format.
+pub fn run_query(
+    conn: &Connection,
+    sql: &str,
+    params: &Params,
+    format: OutputFormat,
+    output: Option<&Path>,
+) -> Result<()> {
+    let batches = execute_query(conn, sql, params)?;
+    format::write_result(&batches, format, output)
+}
+
+/// Pretty-print a query result using Arrow
+pub fn run_query_pretty(conn: &Connection, sql: &str, params: &Params) -> Result<()> {
+    run_query(conn, sql, params, OutputFormat::Table, None)
+}
+
+/// List the tables in the database, mirroring `sqlite3`'s `.tables`
+pub fn list_tables(conn: &Connection) -> Result<()> {
+    run_query_pretty(
+        conn,
+        "SELECT table_name FROM information_schema.tables \
+         WHERE table_schema = 'main' ORDER BY table_name",
+        &Params::new(),
+    )
+}
+
+/// Describe the columns of `table`, or of every table when `table` is `None`
+pub fn describe_schema(conn: &Connection, table: Option<&str>) -> Result<()> {
+    let sql = match table {
+        Some(table) => format!(
+            "SELECT table_name, column_name, data_type, is_nullable \
+             FROM information_schema.columns \
+             WHERE table_schema = 'main' AND table_name = '{}' \
+             ORDER BY ordinal_position",
+            table.replace('\'', "''"),
+        ),
+        None => "SELECT table_name, column_name, data_type, is_nullable \
+                  FROM information_schema.columns WHERE table_schema = 'main' \
+                  ORDER BY table_name, ordinal_position"
+            .to_string(),
+    };
+    run_query_pretty(conn, &sql, &Params::new())
+}
+
+/// Profile a table or query with DuckDB's `SUMMARIZE`: per-column type,
+/// min/max, approximate distinct count, null percentage, and quantiles.
+pub fn summarize(conn: &Connection, table_or_sql: &str) -> Result<()> {
+    let sql = if validate_identifier(table_or_sql).is_ok() {
+        format!(r#"SUMMARIZE "{table_or_sql}""#)
+    } else {
+        format!("SUMMARIZE ({table_or_sql})")
+    };
+    run_query_pretty(conn, &sql, &Params::new())
+}
+
+/// Export the whole database to `dir` via DuckDB's `EXPORT DATABASE`
+pub fn backup_database(conn: &Connection, dir: &str) -> Result<()> {
+    let escaped = dir.replace('\'', "''");
+    conn.execute_batch(&format!("EXPORT DATABASE '{escaped}' (FORMAT PARQUET);"))
+        .wrap_err_with(|| format!("EXPORT DATABASE to `{dir}` failed"))
+}
+
+/// Reload the whole database from `dir` via DuckDB's `IMPORT DATABASE`
+pub fn restore_database(conn: &Connection, dir: &str) -> Result<()> {
+    let escaped = dir.replace('\'', "''");
+    conn.execute_batch(&format!("IMPORT DATABASE '{escaped}';"))
+        .wrap_err_with(|| format!("IMPORT DATABASE from `{dir}` failed"))
+}
+
+/// File formats [`import`] knows how to load, auto-detected from the file
+/// extension unless the caller passes `--format` explicitly.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportFormat {
+    Csv,
+    Parquet,
+    Json,
+    Ndjson,
+}
+
+impl std::fmt::Display for ImportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ImportFormat::Csv => "csv",
+            ImportFormat::Parquet => "parquet",
+            ImportFormat::Json => "json",
+            ImportFormat::Ndjson => "ndjson",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Guess the import format from `path`'s extension
+pub fn detect_import_format(path: &str) -> Result<ImportFormat> {
+    match Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("csv") | Some("tsv") => Ok(ImportFormat::Csv),
+        Some("parquet") => Ok(ImportFormat::Parquet),
+        Some("json") => Ok(ImportFormat::Json),
+        Some("ndjson") => Ok(ImportFormat::Ndjson),
+        _ => Err(eyre!(
+            "can't detect an import format from `{path}`; pass --format explicitly"
+        )),
+    }
+}
+
+/// Import `path` into `table`, dispatching to the DuckDB reader for `format`.
+/// `--delimiter`/`--header` only make sense for CSV and are rejected otherwise.
+pub fn import(
+    conn: &Connection,
+    table: &str,
+    path: &str,
+    format: ImportFormat,
+    delimiter: Option<char>,
+    header: bool,
+) -> Result<()> {
+    validate_identifier(table)?;
+
+    match format {
+        ImportFormat::Csv => {
+            import_csv(conn, table, path, delimiter.unwrap_or_else(|| default_csv_delimiter(path)), header)
+        }
+        ImportFormat::Parquet => {
+            reject_csv_only_options(format, delimiter, header)?;
+            import_via_reader(conn, table, path, "read_parquet")
+        }
+        ImportFormat::Json => {
+            reject_csv_only_options(format, delimiter, header)?;
+            import_via_reader(conn, table, path, "read_json_auto")
+        }
+        ImportFormat::Ndjson => {
+            reject_csv_only_options(format, delimiter, header)?;
+            import_via_reader(conn, table, path, "read_ndjson_auto")
+        }
+    }
+}
+
+/// Default `--delimiter` for a CSV import when none was given: `.tsv` gets a
+/// tab, everything else (including a bare `.csv`) keeps the comma default.
+fn default_csv_delimiter(path: &str) -> char {
+    match Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("tsv") => '\t',
+        _ => ',',
+    }
+}
+
+fn reject_csv_only_options(
+    format: ImportFormat,
+    delimiter: Option<char>,
+    header: bool,
+) -> Result<()> {
+    if delimiter.is_some() {
+        return Err(eyre!("--delimiter only applies to CSV imports, not {format}"));
+    }
+    if header {
+        return Err(eyre!("--header only applies to CSV imports, not {format}"));
+    }
+    Ok(())
+}
+
+/// The file carries its own schema (Parquet/JSON), so the table is created with
+/// `CREATE TABLE AS SELECT * FROM <reader_fn>('path')` and then filled with an
+/// `INSERT ... SELECT`; no COPY step needed. An existing table is appended to.
+fn import_via_reader(conn: &Connection, table: &str, path: &str, reader_fn: &str) -> Result<()> {
+    let quoted_path = sql_quote(path);
+
+    let create_sql = format!(
+        r#"CREATE TABLE IF NOT EXISTS "{table}" AS SELECT * FROM {reader_fn}({quoted_path}) LIMIT 0;"#
+    );
+    conn.execute(&create_sql, [])
+        .wrap_err_with(|| format!("failed to create table `{table}` from `{path}`"))?;
+
+    let insert_sql = format!(r#"INSERT INTO "{table}" SELECT * FROM {reader_fn}({quoted_path});"#);
+    conn.execute(&insert_sql, [])
+        .wrap_err_with(|| format!("failed to import `{path}` into table `{table}`"))?;
+
+    println!("Imported `{path}` into table `{table}`");
+    Ok(())
+}
+
+/// Import CSV via DuckDB COPY. The table name can't be bound as a parameter (it's
+/// an identifier, not a value), and neither can the path or delimiter: `COPY`'s
+/// file target and `read_csv_auto`'s schema sniffing both need a literal at bind
+/// time, not a prepared-statement parameter substituted at execute time. So all
+/// three are interpolated, with the path quoted via [`sql_quote`].
+fn import_csv(
+    conn: &Connection,
+    table: &str,
+    csv_path: &str,
+    delimiter: char,
+    header: bool,
+) -> Result<()> {
+    let header = if header { "TRUE" } else { "FALSE" };
+    let quoted_path = sql_quote(csv_path);
+    let quoted_delim = sql_quote(&delimiter.to_string());
+
+    // Create table if not exists using DuckDB's auto-detection
+    let create_sql = format!(
+        r#"
+        CREATE TABLE IF NOT EXISTS "{table}" AS
+        SELECT * FROM read_csv_auto({quoted_path}, HEADER {header}, DELIM {quoted_delim})
+        LIMIT 0;
+        "#
+    );
+    conn.execute(&create_sql, [])
+        .wrap_err("failed to create table from CSV schema")?;
+
+    let copy_sql = format!(
+        r#"
+        COPY "{table}" FROM {quoted_path}
+        (FORMAT 'csv', HEADER {header}, DELIMITER {quoted_delim});
+        "#
+    );
+    conn.execute(&copy_sql, [])
+        .wrap_err("COPY FROM CSV failed")?;
+
+    println!(
+        "Imported CSV `{csv}` into table `{table}`",
+        csv = csv_path,
+        table = table
+    );
+    Ok(())
+}
+
+/// Export query result to CSV using COPY ( SELECT ... ) TO ..., binding the
+/// caller's `--param`s (used inside `sql`) as named parameters. The output path
+/// is `COPY`'s own file target, which DuckDB needs as a literal at bind time, so
+/// it's quoted via [`sql_quote`] and interpolated rather than bound.
+pub fn export_csv(conn: &Connection, sql: &str, csv_path: &str, params: &Params) -> Result<()> {
+    // Ensure directory exists
+    if let Some(parent) = Path::new(csv_path).parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)
+                .wrap_err_with(|| format!("failed to create directory `{}`", parent.display()))?;
+        }
+    }
+
+    // Touch file early to give friendlier error if path is bad
+    File::create(csv_path)
+        .wrap_err_with(|| format!("failed to create output file `{csv_path}`"))?;
+
+    let quoted_path = sql_quote(csv_path);
+    let copy_sql = format!(
+        r#"
+        COPY (
+            {sql}
+        )
+        TO {quoted_path}
+        (FORMAT 'csv', HEADER TRUE);
+        "#
+    );
+
+    if params.is_empty() {
+        conn.execute(&copy_sql, []).wrap_err("COPY TO CSV failed")?;
+    } else {
+        let bound = bind_named(params);
+        conn.execute(&copy_sql, to_sql_refs(&bound).as_slice())
+            .wrap_err("COPY TO CSV failed")?;
+    }
+
+    println!("Exported query result to `{csv}`", csv = csv_path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_identifier_accepts_plain_names() {
+        assert!(validate_identifier("t").is_ok());
+        assert!(validate_identifier("my_table").is_ok());
+        assert!(validate_identifier("_private1").is_ok());
+    }
+
+    #[test]
+    fn validate_identifier_rejects_injection_attempts() {
+        assert!(validate_identifier("t\"; DROP TABLE users; --").is_err());
+        assert!(validate_identifier("t t").is_err());
+        assert!(validate_identifier("t;").is_err());
+        assert!(validate_identifier("1table").is_err());
+        assert!(validate_identifier("").is_err());
+    }
+}
@@ -0,0 +1,208 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use clap::ValueEnum;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use duckdb::arrow::array::{
+    Array, BooleanArray, Float32Array, Float64Array, Int16Array, Int32Array, Int64Array,
+    Int8Array, LargeStringArray, StringArray, UInt16Array, UInt32Array, UInt64Array, UInt8Array,
+};
+use duckdb::arrow::csv::WriterBuilder as CsvWriterBuilder;
+use duckdb::arrow::datatypes::{DataType, Schema};
+use duckdb::arrow::record_batch::RecordBatch;
+use duckdb::arrow::util::display::{ArrayFormatter, FormatOptions};
+use duckdb::arrow::util::pretty::print_batches;
+use parquet::arrow::ArrowWriter;
+use serde_json::{Map, Value};
+
+/// Output formats for `Query`/`Shell` results, selected with `--format`/`\format`.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Pretty-printed Arrow table (the original behaviour)
+    #[default]
+    Table,
+    /// Comma-separated values
+    Csv,
+    /// Tab-separated values
+    Tsv,
+    /// A single JSON array of row objects
+    Json,
+    /// One JSON object per line
+    Ndjson,
+    /// Apache Parquet (requires `--output`)
+    Parquet,
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            OutputFormat::Table => "table",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Tsv => "tsv",
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Parquet => "parquet",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Write `batches` in `format`, to `output` if given or to stdout otherwise.
+/// `Table` always prints to stdout and ignores `output`.
+pub fn write_result(
+    batches: &[RecordBatch],
+    format: OutputFormat,
+    output: Option<&Path>,
+) -> Result<()> {
+    match format {
+        OutputFormat::Table => write_table(batches),
+        OutputFormat::Csv => write_delimited(batches, b',', output),
+        OutputFormat::Tsv => write_delimited(batches, b'\t', output),
+        OutputFormat::Json => write_json(batches, output),
+        OutputFormat::Ndjson => write_ndjson_rows(batches, &mut sink(output)?),
+        OutputFormat::Parquet => write_parquet(
+            batches,
+            output.ok_or_else(|| eyre!("--format parquet requires --output <path>"))?,
+        ),
+    }
+}
+
+/// Serialize `batches` as newline-delimited JSON objects, one per row, to `path`.
+/// Used by the shell's `\save <file>` meta-command.
+pub fn write_ndjson(batches: &[RecordBatch], path: &Path) -> Result<()> {
+    let file = File::create(path)
+        .wrap_err_with(|| format!("failed to create output file `{}`", path.display()))?;
+    write_ndjson_rows(batches, &mut BufWriter::new(file))
+}
+
+fn sink(output: Option<&Path>) -> Result<Box<dyn Write>> {
+    match output {
+        Some(path) => {
+            let file = File::create(path)
+                .wrap_err_with(|| format!("failed to create output file `{}`", path.display()))?;
+            Ok(Box::new(BufWriter::new(file)))
+        }
+        None => Ok(Box::new(io::stdout())),
+    }
+}
+
+fn write_table(batches: &[RecordBatch]) -> Result<()> {
+    if batches.is_empty() {
+        println!("OK (no rows)");
+    } else {
+        print_batches(batches).wrap_err("failed to pretty-print result")?;
+    }
+    Ok(())
+}
+
+fn write_delimited(batches: &[RecordBatch], delimiter: u8, output: Option<&Path>) -> Result<()> {
+    let mut writer = CsvWriterBuilder::new()
+        .with_delimiter(delimiter)
+        .with_header(true)
+        .build(sink(output)?);
+    for batch in batches {
+        writer.write(batch).wrap_err("failed to write delimited output")?;
+    }
+    Ok(())
+}
+
+fn write_ndjson_rows<W: Write>(batches: &[RecordBatch], writer: &mut W) -> Result<()> {
+    for row in row_objects(batches) {
+        serde_json::to_writer(&mut *writer, &row).wrap_err("failed to serialize row to JSON")?;
+        writer.write_all(b"\n").wrap_err("failed to write output")?;
+    }
+    Ok(())
+}
+
+fn write_json(batches: &[RecordBatch], output: Option<&Path>) -> Result<()> {
+    let mut writer = sink(output)?;
+    let rows = Value::Array(row_objects(batches));
+    serde_json::to_writer_pretty(&mut writer, &rows)
+        .wrap_err("failed to serialize result to JSON")?;
+    writer
+        .write_all(b"\n")
+        .wrap_err("failed to write output")?;
+    Ok(())
+}
+
+fn row_objects(batches: &[RecordBatch]) -> Vec<Value> {
+    let mut rows = Vec::new();
+    for batch in batches {
+        let schema = batch.schema();
+        for row in 0..batch.num_rows() {
+            let mut object = Map::new();
+            for (col_idx, field) in schema.fields().iter().enumerate() {
+                let value = array_value_to_json(batch.column(col_idx).as_ref(), row);
+                object.insert(field.name().clone(), value);
+            }
+            rows.push(Value::Object(object));
+        }
+    }
+    rows
+}
+
+pub fn write_parquet(batches: &[RecordBatch], output: &Path) -> Result<()> {
+    let file = File::create(output)
+        .wrap_err_with(|| format!("failed to create output file `{}`", output.display()))?;
+    let schema: Arc<Schema> = batches
+        .first()
+        .map(|batch| batch.schema())
+        .unwrap_or_else(|| Arc::new(Schema::empty()));
+
+    let mut writer =
+        ArrowWriter::try_new(file, schema, None).wrap_err("failed to create parquet writer")?;
+    for batch in batches {
+        writer
+            .write(batch)
+            .wrap_err("failed to write parquet batch")?;
+    }
+    writer.close().wrap_err("failed to finalize parquet file")?;
+    Ok(())
+}
+
+/// Map a single Arrow array slot to a JSON value, falling back to the column's
+/// display rendering for types we don't special-case (dates, decimals, ...).
+pub fn array_value_to_json(array: &dyn Array, row: usize) -> Value {
+    if array.is_null(row) {
+        return Value::Null;
+    }
+
+    match array.data_type() {
+        DataType::Boolean => {
+            Value::Bool(array.as_any().downcast_ref::<BooleanArray>().unwrap().value(row))
+        }
+        DataType::Int8 => array.as_any().downcast_ref::<Int8Array>().unwrap().value(row).into(),
+        DataType::Int16 => array.as_any().downcast_ref::<Int16Array>().unwrap().value(row).into(),
+        DataType::Int32 => array.as_any().downcast_ref::<Int32Array>().unwrap().value(row).into(),
+        DataType::Int64 => array.as_any().downcast_ref::<Int64Array>().unwrap().value(row).into(),
+        DataType::UInt8 => array.as_any().downcast_ref::<UInt8Array>().unwrap().value(row).into(),
+        DataType::UInt16 => {
+            array.as_any().downcast_ref::<UInt16Array>().unwrap().value(row).into()
+        }
+        DataType::UInt32 => {
+            array.as_any().downcast_ref::<UInt32Array>().unwrap().value(row).into()
+        }
+        DataType::UInt64 => {
+            array.as_any().downcast_ref::<UInt64Array>().unwrap().value(row).into()
+        }
+        DataType::Float32 => {
+            array.as_any().downcast_ref::<Float32Array>().unwrap().value(row).into()
+        }
+        DataType::Float64 => {
+            array.as_any().downcast_ref::<Float64Array>().unwrap().value(row).into()
+        }
+        DataType::Utf8 => Value::String(
+            array.as_any().downcast_ref::<StringArray>().unwrap().value(row).to_string(),
+        ),
+        DataType::LargeUtf8 => Value::String(
+            array.as_any().downcast_ref::<LargeStringArray>().unwrap().value(row).to_string(),
+        ),
+        _ => {
+            let formatter = ArrayFormatter::try_new(array, &FormatOptions::default())
+                .expect("array formatter");
+            Value::String(formatter.value(row).to_string())
+        }
+    }
+}
@@ -1,12 +1,17 @@
-use std::fs::File;
-use std::io::{self};
-use std::path::Path;
+mod db;
+mod format;
+mod shell;
+mod sql;
+
+use std::io;
+use std::path::PathBuf;
 
 use clap::{Parser, Subcommand, ValueEnum, CommandFactory};
 use clap_complete::{Shell as CompleteShell, generate};
-use color_eyre::eyre::{Result, WrapErr};
-use duckdb::Connection;
-use rustyline::DefaultEditor;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+
+use db::ImportFormat;
+use format::OutputFormat;
 
 /// Top-level CLI
 #[derive(Parser, Debug)]
@@ -49,9 +54,24 @@ enum Commands {
     Query {
         /// Path to DuckDB database
         db: String,
-        /// SQL to run (everything after <db> is concatenated)
-        #[arg(required = true)]
+        /// SQL to run (everything after <db> is concatenated); omit with `--file`/`--stdin`
         sql: Vec<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+        /// Write the result here instead of stdout (required for `--format parquet`;
+        /// ignored by `--format table`, which always prints to stdout)
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Bind a named parameter as `$name` (repeatable)
+        #[arg(long = "param", value_name = "name=value")]
+        params: Vec<String>,
+        /// Run a `;`-separated script from this file as a single transaction
+        #[arg(long, conflicts_with_all = ["sql", "stdin"])]
+        file: Option<PathBuf>,
+        /// Run a `;`-separated script read from stdin as a single transaction
+        #[arg(long, conflicts_with_all = ["sql", "file"])]
+        stdin: bool,
     },
 
     /// Start an interactive SQL shell
@@ -60,18 +80,21 @@ enum Commands {
         db: String,
     },
 
-    /// Import CSV into a table using COPY
+    /// Import a CSV, Parquet, JSON, or NDJSON file into a table
     Import {
         /// Path to DuckDB database
         db: String,
         /// Target table name (will be created if not exists)
         table: String,
-        /// CSV file to import
-        csv_path: String,
-        /// Delimiter (default ',')
-        #[arg(long, default_value_t = ',')]
-        delimiter: char,
-        /// Treat first row as header
+        /// File to import
+        path: String,
+        /// Import format; guessed from the file extension if omitted
+        #[arg(long, value_enum)]
+        format: Option<ImportFormat>,
+        /// Delimiter (CSV only, default ',', or a tab for `.tsv` files)
+        #[arg(long)]
+        delimiter: Option<char>,
+        /// Treat first row as header (CSV only)
         #[arg(long)]
         header: bool,
     },
@@ -85,6 +108,18 @@ enum Commands {
         sql: Vec<String>,
         /// Output CSV file
         csv_path: String,
+        /// Bind a named parameter as `$name` (repeatable)
+        #[arg(long = "param", value_name = "name=value")]
+        params: Vec<String>,
+    },
+
+    /// Summarize a table or query: per-column type, min/max, null %, quantiles, ...
+    Summary {
+        /// Path to DuckDB database
+        db: String,
+        /// A bare table name, or a full SQL query (everything is concatenated)
+        #[arg(required = true)]
+        table_or_sql: Vec<String>,
     },
 
     /// Generate shell completion script
@@ -102,29 +137,68 @@ fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Query { db, sql } => {
-            let sql = sql.join(" ");
-            let conn = open_db(&db)?;
-            run_query_pretty(&conn, &sql)?;
+        Commands::Query {
+            db,
+            sql,
+            format,
+            output,
+            params,
+            file,
+            stdin,
+        } => {
+            let mut conn = db::open_db(&db)?;
+            let params = db::parse_params(&params)?;
+
+            if let Some(path) = file {
+                let script = std::fs::read_to_string(&path)
+                    .wrap_err_with(|| format!("failed to read script `{}`", path.display()))?;
+                db::run_script(&mut conn, &script, &params)?;
+            } else if stdin {
+                let mut script = String::new();
+                io::Read::read_to_string(&mut io::stdin(), &mut script)
+                    .wrap_err("failed to read SQL script from stdin")?;
+                db::run_script(&mut conn, &script, &params)?;
+            } else if sql.is_empty() {
+                return Err(eyre!("no SQL given; pass it inline, or use --file/--stdin"));
+            } else {
+                let sql = sql.join(" ");
+                db::run_query(&conn, &sql, &params, format, output.as_deref())?;
+            }
         }
         Commands::Shell { db } => {
-            let conn = open_db(&db)?;
-            interactive_shell(conn)?;
+            let conn = db::open_db(&db)?;
+            shell::interactive_shell(conn)?;
         }
         Commands::Import {
             db,
             table,
-            csv_path,
+            path,
+            format,
             delimiter,
             header,
         } => {
-            let conn = open_db(&db)?;
-            import_csv(&conn, &table, &csv_path, delimiter, header)?;
+            let conn = db::open_db(&db)?;
+            let format = match format {
+                Some(format) => format,
+                None => db::detect_import_format(&path)?,
+            };
+            db::import(&conn, &table, &path, format, delimiter, header)?;
         }
-        Commands::Export { db, sql, csv_path } => {
-            let conn = open_db(&db)?;
+        Commands::Export {
+            db,
+            sql,
+            csv_path,
+            params,
+        } => {
+            let conn = db::open_db(&db)?;
             let sql = sql.join(" ");
-            export_csv(&conn, &sql, &csv_path)?;
+            let params = db::parse_params(&params)?;
+            db::export_csv(&conn, &sql, &csv_path, &params)?;
+        }
+        Commands::Summary { db, table_or_sql } => {
+            let conn = db::open_db(&db)?;
+            let table_or_sql = table_or_sql.join(" ");
+            db::summarize(&conn, &table_or_sql)?;
         }
         Commands::Completions { shell } => {
             let mut cmd = Cli::command();
@@ -136,156 +210,3 @@ fn main() -> Result<()> {
 
     Ok(())
 }
-
-/// Open (or create) a DuckDB database
-fn open_db(path: &str) -> Result<Connection> {
-    Connection::open(path).wrap_err_with(|| format!("failed to open DuckDB database at {path}"))
-}
-
-/// Pretty-print a query result using Arrow
-fn run_query_pretty(conn: &Connection, sql: &str) -> Result<()> {
-    use duckdb::arrow::util::pretty::print_batches;
-
-    let mut stmt = conn
-        .prepare(sql)
-        .wrap_err_with(|| format!("failed to prepare query: {sql}"))?;
-
-    let arrow = stmt.query_arrow([]).wrap_err("arrow query failed")?;
-    let batches: Vec<_> = arrow.collect();
-
-
-    if batches.is_empty() {
-        println!("OK (no rows)");
-    } else {
-        print_batches(&batches).wrap_err("failed to pretty-print result")?;
-    }
-    Ok(())
-}
-
-/// Super-minimal interactive shell using rustyline
-fn interactive_shell(conn: Connection) -> Result<()> {
-    println!("Connected to DuckDB. Enter SQL, or `\\q` to quit.");
-
-    let mut readline_editor = DefaultEditor::new()?;
-
-    loop {
-        let line = readline_editor.readline("duckdb> ");
-
-        let line = match line {
-            Ok(line) => {
-                let trimmed = line.trim();
-                if trimmed.is_empty() {
-                    continue;
-                }
-                let _ = readline_editor.add_history_entry(trimmed);
-                trimmed.to_string()
-            }
-            Err(rustyline::error::ReadlineError::Interrupted)
-            | Err(rustyline::error::ReadlineError::Eof) => {
-                println!();
-                break;
-            }
-            Err(e) => {
-                eprintln!("readline error: {e}");
-                break;
-            }
-        };
-
-        if line == "\\q" {
-            break;
-        }
-
-        // Allow multiple statements separated by ';'
-        for stmt in line.split(';').map(str::trim).filter(|s| !s.is_empty()) {
-            if let Err(err) = run_query_pretty(&conn, stmt) {
-                eprintln!("error: {err:?}");
-            }
-        }
-    }
-
-    Ok(())
-}
-
-/// Import CSV via DuckDB COPY
-fn import_csv(
-    conn: &Connection,
-    table: &str,
-    csv_path: &str,
-    delimiter: char,
-    header: bool,
-) -> Result<()> {
-    // Simple-ish escaping for quote characters
-    let escaped_path = csv_path.replace('\'', "''");
-    let escaped_table = table.replace('"', "\"\"");
-
-    // Create table if not exists using DuckDB's auto-detection
-    let create_sql = format!(
-        r#"
-        CREATE TABLE IF NOT EXISTS "{table}" AS
-        SELECT * FROM read_csv_auto('{path}', HEADER {header}, DELIM '{delim}')
-        LIMIT 0;
-        "#,
-        table = escaped_table,
-        path = escaped_path,
-        header = if header { "TRUE" } else { "FALSE" },
-        delim = delimiter,
-    );
-    conn.execute_batch(&create_sql)
-        .wrap_err("failed to create table from CSV schema")?;
-
-    let copy_sql = format!(
-        r#"
-        COPY "{table}" FROM '{path}'
-        (FORMAT 'csv', HEADER {header}, DELIMITER '{delim}');
-        "#,
-        table = escaped_table,
-        path = escaped_path,
-        header = if header { "TRUE" } else { "FALSE" },
-        delim = delimiter,
-    );
-
-    conn.execute_batch(&copy_sql)
-        .wrap_err("COPY FROM CSV failed")?;
-
-    println!(
-        "Imported CSV `{csv}` into table `{table}`",
-        csv = csv_path,
-        table = table
-    );
-    Ok(())
-}
-
-/// Export query result to CSV using COPY ( SELECT ... ) TO ...
-fn export_csv(conn: &Connection, sql: &str, csv_path: &str) -> Result<()> {
-    // Ensure directory exists
-    if let Some(parent) = Path::new(csv_path).parent() {
-        if !parent.as_os_str().is_empty() {
-            std::fs::create_dir_all(parent)
-                .wrap_err_with(|| format!("failed to create directory `{}`", parent.display()))?;
-        }
-    }
-
-    // Touch file early to give friendlier error if path is bad
-    File::create(csv_path)
-        .wrap_err_with(|| format!("failed to create output file `{csv_path}`"))?;
-
-    let escaped_path = csv_path.replace('\'', "''");
-
-    let copy_sql = format!(
-        r#"
-        COPY (
-            {sql}
-        )
-        TO '{path}'
-        (FORMAT 'csv', HEADER TRUE);
-        "#,
-        sql = sql,
-        path = escaped_path,
-    );
-
-    conn.execute_batch(&copy_sql)
-        .wrap_err("COPY TO CSV failed")?;
-
-    println!("Exported query result to `{csv}`", csv = csv_path);
-    Ok(())
-}
@@ -0,0 +1,248 @@
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+use color_eyre::eyre::{Result, WrapErr};
+use duckdb::Connection;
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::FileHistory;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Editor, Helper};
+
+use crate::db::{self, Params};
+use crate::format::{self, OutputFormat};
+use crate::sql;
+
+const CONTINUATION_PROMPT: &str = "   ...> ";
+
+/// Rustyline helper that keeps reading lines until they form complete
+/// statements, so a `CREATE TABLE`/`WITH` query can span several lines.
+#[derive(Default)]
+struct SqlHelper {
+    continuation: Cell<bool>,
+}
+
+impl Validator for SqlHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        if is_meta_command(ctx.input().trim_start()) || sql::is_complete(ctx.input()) {
+            self.continuation.set(false);
+            Ok(ValidationResult::Valid(None))
+        } else {
+            self.continuation.set(true);
+            Ok(ValidationResult::Incomplete)
+        }
+    }
+}
+
+impl Highlighter for SqlHelper {
+    fn highlight_prompt<'b, 's: 'b, 'p: 'b>(
+        &'s self,
+        prompt: &'p str,
+        default: bool,
+    ) -> Cow<'b, str> {
+        if default && self.continuation.get() {
+            Cow::Borrowed(CONTINUATION_PROMPT)
+        } else {
+            Cow::Borrowed(prompt)
+        }
+    }
+}
+
+impl Completer for SqlHelper {
+    type Candidate = String;
+}
+
+impl Hinter for SqlHelper {
+    type Hint = String;
+}
+
+impl Helper for SqlHelper {}
+
+/// Super-minimal interactive shell using rustyline
+pub fn interactive_shell(mut conn: Connection) -> Result<()> {
+    println!("Connected to DuckDB. Enter SQL, or `\\q` to quit.");
+
+    let mut readline_editor: Editor<SqlHelper, FileHistory> = Editor::new()?;
+    readline_editor.set_helper(Some(SqlHelper::default()));
+    let mut params: Params = Params::new();
+    let mut pending_save: Option<PathBuf> = None;
+    let mut format = OutputFormat::Table;
+
+    loop {
+        let line = readline_editor.readline("duckdb> ");
+
+        let line = match line {
+            Ok(line) => {
+                let trimmed = line.trim();
+                if trimmed.is_empty() {
+                    continue;
+                }
+                let _ = readline_editor.add_history_entry(trimmed);
+                trimmed.to_string()
+            }
+            Err(rustyline::error::ReadlineError::Interrupted)
+            | Err(rustyline::error::ReadlineError::Eof) => {
+                println!();
+                break;
+            }
+            Err(e) => {
+                eprintln!("readline error: {e}");
+                break;
+            }
+        };
+
+        if line == "\\q" {
+            break;
+        }
+
+        // Meta-commands are dispatched before the SQL path, so they never reach
+        // `db::run_query` even if they happen to be malformed.
+        if is_meta_command(&line) {
+            if let Err(err) = dispatch_meta_command(
+                &mut conn,
+                &line,
+                &mut params,
+                &mut pending_save,
+                &mut format,
+            ) {
+                eprintln!("error: {err:?}");
+            }
+            continue;
+        }
+
+        for stmt in sql::split_statements(&line) {
+            if let Some(path) = pending_save.clone() {
+                match save_query_result(&conn, &stmt, &params, &path) {
+                    Ok(()) => pending_save = None,
+                    Err(err) => eprintln!("error: {err:?}"),
+                }
+            } else if let Err(err) = db::run_query(&conn, &stmt, &params, format, None) {
+                eprintln!("error: {err:?}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn is_meta_command(line: &str) -> bool {
+    line.starts_with('\\') || line.starts_with('.')
+}
+
+/// Handle a `\foo`/`.foo` meta-command line, mutating shell session state as needed.
+fn dispatch_meta_command(
+    conn: &mut Connection,
+    line: &str,
+    params: &mut Params,
+    pending_save: &mut Option<PathBuf>,
+    format: &mut OutputFormat,
+) -> Result<()> {
+    let mut parts = line.splitn(2, char::is_whitespace);
+    let command = parts.next().unwrap_or_default();
+    let rest = parts.next().unwrap_or_default().trim();
+
+    match command {
+        "\\set" => {
+            let mut kv = rest.splitn(2, char::is_whitespace);
+            let key = kv.next().unwrap_or_default();
+            let value = kv.next().unwrap_or_default().trim();
+            if key.is_empty() {
+                eprintln!("usage: \\set <key> <value>");
+            } else {
+                params.insert(key.to_string(), value.to_string());
+            }
+        }
+        "\\unset" => {
+            if rest.is_empty() {
+                eprintln!("usage: \\unset <key>");
+            } else {
+                params.remove(rest);
+            }
+        }
+        "\\params" => {
+            if params.is_empty() {
+                println!("(no parameters set)");
+            } else {
+                for (key, value) in params.iter() {
+                    println!("${key} = {value}");
+                }
+            }
+        }
+        "\\save" => {
+            *pending_save = if rest.is_empty() {
+                None
+            } else {
+                Some(PathBuf::from(rest))
+            };
+        }
+        "\\backup" => {
+            if rest.is_empty() {
+                eprintln!("usage: \\backup <directory>");
+            } else {
+                db::backup_database(conn, rest)?;
+                println!("Backed up database to `{rest}`");
+            }
+        }
+        "\\restore" => {
+            if rest.is_empty() {
+                eprintln!("usage: \\restore <directory>");
+            } else {
+                db::restore_database(conn, rest)?;
+                println!("Restored database from `{rest}`");
+            }
+        }
+        "\\i" => {
+            if rest.is_empty() {
+                eprintln!("usage: \\i <file>");
+            } else {
+                let script = std::fs::read_to_string(rest)
+                    .wrap_err_with(|| format!("failed to read script `{rest}`"))?;
+                db::run_script(conn, &script, &*params)?;
+            }
+        }
+        "\\format" => {
+            if rest.is_empty() {
+                println!("current format: {format}");
+            } else {
+                match OutputFormat::from_str(rest, true) {
+                    Ok(OutputFormat::Parquet) => eprintln!(
+                        "parquet is not supported for \\format (it needs an --output path); use \\save <path>.parquet instead"
+                    ),
+                    Ok(parsed) => *format = parsed,
+                    Err(_) => eprintln!(
+                        "unknown format `{rest}` (expected table, csv, tsv, json, or ndjson)"
+                    ),
+                }
+            }
+        }
+        ".tables" => db::list_tables(conn)?,
+        ".schema" => {
+            let table = if rest.is_empty() { None } else { Some(rest) };
+            db::describe_schema(conn, table)?;
+        }
+        _ => eprintln!("unknown command: {command}"),
+    }
+
+    Ok(())
+}
+
+/// Run `sql`, writing its Arrow result to `path` instead of pretty-printing it.
+fn save_query_result(conn: &Connection, sql: &str, params: &Params, path: &PathBuf) -> Result<()> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("csv") => db::export_csv(conn, sql, &path.to_string_lossy(), params)?,
+        Some("parquet") => {
+            let batches = db::execute_query(conn, sql, params)?;
+            format::write_parquet(&batches, path)?;
+            println!("Saved query result to `{}`", path.display());
+        }
+        _ => {
+            let batches = db::execute_query(conn, sql, params)?;
+            format::write_ndjson(&batches, path)?;
+            println!("Saved query result to `{}`", path.display());
+        }
+    }
+    Ok(())
+}
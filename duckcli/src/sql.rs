@@ -0,0 +1,203 @@
+//! A quote/comment-aware scanner used to tell complete SQL statements apart
+//! from ones that are still being typed or pasted in, without a full parser.
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Normal,
+    SingleQuoted,
+    DoubleQuoted,
+    LineComment,
+    BlockComment,
+}
+
+/// Scan `sql` left to right and report the end state plus the byte offset
+/// just past the last top-level (i.e. not quoted/commented) `;`.
+fn scan(sql: &str) -> (State, usize) {
+    let chars: Vec<(usize, char)> = sql.char_indices().collect();
+    let mut state = State::Normal;
+    let mut last_semicolon_end = 0usize;
+    let mut i = 0usize;
+
+    while i < chars.len() {
+        let (byte_idx, c) = chars[i];
+        let next = chars.get(i + 1).map(|&(_, c)| c);
+
+        match state {
+            State::Normal => match c {
+                '\'' => state = State::SingleQuoted,
+                '"' => state = State::DoubleQuoted,
+                '-' if next == Some('-') => {
+                    state = State::LineComment;
+                    i += 1;
+                }
+                '/' if next == Some('*') => {
+                    state = State::BlockComment;
+                    i += 1;
+                }
+                ';' => last_semicolon_end = byte_idx + c.len_utf8(),
+                _ => {}
+            },
+            State::SingleQuoted => {
+                if c == '\'' {
+                    if next == Some('\'') {
+                        i += 1; // escaped '' inside the string, stay quoted
+                    } else {
+                        state = State::Normal;
+                    }
+                }
+            }
+            State::DoubleQuoted => {
+                if c == '"' {
+                    if next == Some('"') {
+                        i += 1; // escaped "" inside the identifier, stay quoted
+                    } else {
+                        state = State::Normal;
+                    }
+                }
+            }
+            State::LineComment => {
+                if c == '\n' {
+                    state = State::Normal;
+                }
+            }
+            State::BlockComment => {
+                if c == '*' && next == Some('/') {
+                    state = State::Normal;
+                    i += 1;
+                }
+            }
+        }
+        i += 1;
+    }
+
+    (state, last_semicolon_end)
+}
+
+/// Whether `sql` consists of one or more complete statements: every quote and
+/// comment is closed, and nothing but whitespace follows the last top-level `;`.
+pub fn is_complete(sql: &str) -> bool {
+    let (state, last_semicolon_end) = scan(sql);
+    state == State::Normal && sql[last_semicolon_end..].trim().is_empty()
+}
+
+/// Split `sql` into its top-level statements, ignoring `;` inside quotes or comments.
+/// A trailing fragment with no terminating `;` is included as-is.
+pub fn split_statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut start = 0usize;
+    let mut state = State::Normal;
+    let chars: Vec<(usize, char)> = sql.char_indices().collect();
+    let mut i = 0usize;
+
+    while i < chars.len() {
+        let (byte_idx, c) = chars[i];
+        let next = chars.get(i + 1).map(|&(_, c)| c);
+
+        match state {
+            State::Normal => match c {
+                '\'' => state = State::SingleQuoted,
+                '"' => state = State::DoubleQuoted,
+                '-' if next == Some('-') => {
+                    state = State::LineComment;
+                    i += 1;
+                }
+                '/' if next == Some('*') => {
+                    state = State::BlockComment;
+                    i += 1;
+                }
+                ';' => {
+                    statements.push(sql[start..byte_idx].trim().to_string());
+                    start = byte_idx + c.len_utf8();
+                }
+                _ => {}
+            },
+            State::SingleQuoted => {
+                if c == '\'' {
+                    if next == Some('\'') {
+                        i += 1;
+                    } else {
+                        state = State::Normal;
+                    }
+                }
+            }
+            State::DoubleQuoted => {
+                if c == '"' {
+                    if next == Some('"') {
+                        i += 1;
+                    } else {
+                        state = State::Normal;
+                    }
+                }
+            }
+            State::LineComment => {
+                if c == '\n' {
+                    state = State::Normal;
+                }
+            }
+            State::BlockComment => {
+                if c == '*' && next == Some('/') {
+                    state = State::Normal;
+                    i += 1;
+                }
+            }
+        }
+        i += 1;
+    }
+
+    let tail = sql[start..].trim();
+    if !tail.is_empty() {
+        statements.push(tail.to_string());
+    }
+
+    statements.retain(|s| !s.is_empty());
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn semicolon_inside_quotes_is_not_a_terminator() {
+        assert!(!is_complete("SELECT ';'"));
+        assert!(is_complete("SELECT ';';"));
+        assert_eq!(split_statements("SELECT ';'; SELECT 1;"), vec!["SELECT ';'", "SELECT 1"]);
+    }
+
+    #[test]
+    fn doubled_quote_escapes_stay_inside_the_string() {
+        assert!(!is_complete("SELECT 'it''s; fine'"));
+        assert!(is_complete("SELECT 'it''s; fine';"));
+        assert_eq!(
+            split_statements(r#"SELECT "a""b;c";"#),
+            vec![r#"SELECT "a""b;c""#]
+        );
+    }
+
+    #[test]
+    fn line_comment_hides_its_semicolon() {
+        assert!(!is_complete("SELECT 1 -- ;\n"));
+        assert!(is_complete("SELECT 1 -- ;\n;"));
+        assert_eq!(
+            split_statements("SELECT 1; -- trailing comment\nSELECT 2;"),
+            vec!["SELECT 1", "-- trailing comment\nSELECT 2"]
+        );
+    }
+
+    #[test]
+    fn block_comment_hides_its_semicolon() {
+        assert!(!is_complete("SELECT /* ; */ 1"));
+        assert!(is_complete("SELECT /* ; */ 1;"));
+        assert_eq!(split_statements("SELECT /* ; */ 1;"), vec!["SELECT /* ; */ 1"]);
+    }
+
+    #[test]
+    fn trailing_statement_without_semicolon_is_incomplete_but_still_split() {
+        assert!(!is_complete("SELECT 1"));
+        assert!(is_complete("SELECT 1;"));
+        assert_eq!(
+            split_statements("SELECT 1; SELECT 2"),
+            vec!["SELECT 1", "SELECT 2"]
+        );
+    }
+}